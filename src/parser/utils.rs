@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::str;
 
 extern crate inflector;
@@ -12,6 +13,42 @@ use crate::parser::parser::parse_node;
 use crate::parser::types::{RsEntity, StructField};
 use crate::parser::xsd_elements::{ElementType, XsdNode};
 
+/// Generator-wide configuration, threaded through parsing and code
+/// generation instead of global state.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub type_map: TypeMap,
+
+    /// When set, `match_type` maps `xs:date`/`xs:dateTime`/`xs:decimal`/...
+    /// to the wrappers from [`typed_wrapper_definitions`] instead of
+    /// degrading them to `String`/`f64`.
+    pub emit_typed_wrappers: bool,
+
+    /// Which [`DeriveBackend`] struct derives and field attributes go
+    /// through. Defaults to yaserde.
+    pub backend: Backend,
+}
+
+/// Overridable mapping from XSD QName (e.g. `"xs:decimal"`) to the Rust
+/// type generated for it; `match_type` consults this before its built-in
+/// table.
+#[derive(Debug, Clone, Default)]
+pub struct TypeMap {
+    overrides: HashMap<String, Cow<'static, str>>,
+}
+
+impl TypeMap {
+    /// Overrides the Rust type generated for `xsd_name`.
+    pub fn with_override<T: Into<Cow<'static, str>>>(mut self, xsd_name: &str, rust_type: T) -> Self {
+        self.overrides.insert(xsd_name.to_string(), rust_type.into());
+        self
+    }
+
+    pub fn get(&self, xsd_name: &str) -> Option<Cow<'static, str>> {
+        self.overrides.get(xsd_name).cloned()
+    }
+}
+
 pub fn split_comment_line(s: &str, max_len: usize, indent: usize) -> String {
     let indent_str = " ".repeat(indent);
 
@@ -39,7 +76,11 @@ pub fn get_formatted_comment(doc: Option<&str>) -> String {
         .fold(String::new(), |x, y| (x + &y))
 }
 
-pub fn match_type(type_name: &str, target_ns: Option<&roxmltree::Namespace>) -> Cow<'static, str> {
+pub fn match_type(
+    type_name: &str,
+    target_ns: Option<&roxmltree::Namespace>,
+    config: &Config,
+) -> Cow<'static, str> {
     fn replace(s: &str) -> String {
         match s.find(':') {
             Some(index) => format!(
@@ -50,6 +91,17 @@ pub fn match_type(type_name: &str, target_ns: Option<&roxmltree::Namespace>) ->
             None => to_pascal_case(s.replace("-", "_").as_str()),
         }
     }
+
+    if let Some(overridden) = config.type_map.get(type_name) {
+        return overridden;
+    }
+
+    if config.emit_typed_wrappers {
+        if let Some(wrapper) = typed_wrapper_name(type_name) {
+            return wrapper.into();
+        }
+    }
+
     match type_name {
         "xs:hexBinary" => "String".into(),
         "xs:base64Binary" => "String".into(),
@@ -139,32 +191,452 @@ pub fn match_type(type_name: &str, target_ns: Option<&roxmltree::Namespace>) ->
     }
 }
 
+/// Maps the XSD built-ins that lose value-level fidelity as `String`/`f64`
+/// to the name of the generated newtype wrapper that should be used
+/// instead. See [`typed_wrapper_definitions`] for the wrapper bodies.
+fn typed_wrapper_name(type_name: &str) -> Option<&'static str> {
+    match type_name {
+        "xs:date" => Some("XsDate"),
+        "xs:time" => Some("XsTime"),
+        "xs:dateTime" => Some("XsDateTime"),
+        "xs:dateTimeStamp" => Some("XsDateTimeStamp"),
+        "xs:gYear" => Some("XsGYear"),
+        "xs:gYearMonth" => Some("XsGYearMonth"),
+        "xs:decimal" => Some("XsDecimal"),
+        "xs:integer" => Some("XsInteger"),
+        _ => None,
+    }
+}
+
+/// Generates a newtype wrapper around a `chrono` type, pinning its
+/// serialize/deserialize to an explicit `format`/`parse_from_str` call
+/// instead of `Display`/`FromStr`, so the XSD lexical representation is
+/// exact rather than whatever chrono's defaults happen to produce.
+fn chrono_wrapper(name: &str, inner: &str, format: &str) -> String {
+    let template = "\
+#[derive(Debug, Clone, PartialEq)]
+pub struct NAME(pub INNER);
+
+impl YaSerialize for NAME {
+    fn serialize<W: std::io::Write>(&self, writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String> {
+        writer
+            .write(xml::writer::XmlEvent::characters(&self.0.format(\"FORMAT\").to_string()))
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl YaDeserialize for NAME {
+    fn deserialize<R: std::io::Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, String> {
+        let text = reader.read_string().map_err(|e| e.to_string())?;
+        INNER::parse_from_str(strip_xsd_timezone(&text), \"FORMAT\")
+            .map(NAME)
+            .map_err(|e| format!(\"invalid NAME '{}': {}\", text, e))
+    }
+}
+
+";
+    template
+        .replace("NAME", name)
+        .replace("INNER", inner)
+        .replace("FORMAT", format)
+}
+
+/// Source of the helper `typed_wrapper_definitions` emits once, so the
+/// `xs:date`/`xs:time`/`xs:gYear`/`xs:gYearMonth` wrappers can accept the
+/// optional timezone suffix (`Z`, `+HH:MM`, `-HH:MM`) that's legal lexical
+/// form for those types but which chrono's plain `NaiveDate`/`NaiveTime`
+/// parsing rejects. The offset is discarded, not preserved: these wrappers
+/// have no zone field to round-trip it through.
+fn xsd_timezone_stripper() -> &'static str {
+    "\
+fn strip_xsd_timezone(text: &str) -> &str {
+    if let Some(stripped) = text.strip_suffix('Z') {
+        return stripped;
+    }
+    if text.len() > 6 {
+        let (body, suffix) = text.split_at(text.len() - 6);
+        let bytes = suffix.as_bytes();
+        if (suffix.starts_with('+') || suffix.starts_with('-')) && bytes[3] == b':' {
+            return body;
+        }
+    }
+    text
+}
+
+"
+}
+
+/// Generates the newtype wrappers (and their hand-written
+/// `YaSerialize`/`YaDeserialize` impls) that back [`typed_wrapper_name`].
+///
+/// `match_type` degrades `xs:date`, `xs:dateTime`, `xs:decimal` and friends
+/// to `String`/`f64` because there's no built-in type that both models the
+/// value numerically and (de)serializes to the exact XSD lexical form. When
+/// `Config::emit_typed_wrappers` is set, this emits one small wrapper per
+/// type instead, each parsing/formatting its XSD lexical representation on
+/// the way in and out, plus the precise-numeric wrappers from
+/// [`precise_numeric_wrapper_definitions`]. Callers splice the result once
+/// into the generated module, alongside the structs that reference these
+/// wrapper names.
+pub fn typed_wrapper_definitions() -> String {
+    let mut out = String::new();
+
+    out += xsd_timezone_stripper();
+
+    out += &chrono_wrapper("XsDate", "chrono::NaiveDate", "%Y-%m-%d");
+    out += &chrono_wrapper("XsTime", "chrono::NaiveTime", "%H:%M:%S%.f");
+
+    for name in &["XsDateTime", "XsDateTimeStamp"] {
+        // `xs:dateTime`/`xs:dateTimeStamp` are RFC 3339 timestamps; chrono's
+        // dedicated rfc3339 parse/format accepts the "Z"/offset suffix and
+        // always writes one, which a generic strftime format string wouldn't.
+        // The value is converted to UTC, so the original offset is not kept.
+        let template = "\
+#[derive(Debug, Clone, PartialEq)]
+pub struct NAME(pub chrono::DateTime<chrono::Utc>);
+
+impl YaSerialize for NAME {
+    fn serialize<W: std::io::Write>(&self, writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String> {
+        writer
+            .write(xml::writer::XmlEvent::characters(&self.0.to_rfc3339()))
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl YaDeserialize for NAME {
+    fn deserialize<R: std::io::Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, String> {
+        let text = reader.read_string().map_err(|e| e.to_string())?;
+        chrono::DateTime::parse_from_rfc3339(&text)
+            .map(|dt| NAME(dt.with_timezone(&chrono::Utc)))
+            .map_err(|e| format!(\"invalid NAME '{}': {}\", text, e))
+    }
+}
+
+";
+        out += &template.replace("NAME", name);
+    }
+
+    // `xs:gYear` is a bare (possibly signed) year, so it wraps an i32
+    // directly rather than a chrono type with no year-only formatter.
+    out += "\
+#[derive(Debug, Clone, PartialEq)]
+pub struct XsGYear(pub i32);
+
+impl YaSerialize for XsGYear {
+    fn serialize<W: std::io::Write>(&self, writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String> {
+        writer.write(xml::writer::XmlEvent::characters(&self.0.to_string())).map_err(|e| e.to_string())
+    }
+}
+
+impl YaDeserialize for XsGYear {
+    fn deserialize<R: std::io::Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, String> {
+        let text = reader.read_string().map_err(|e| e.to_string())?;
+        strip_xsd_timezone(&text).parse().map(XsGYear).map_err(|e| format!(\"invalid xs:gYear '{}': {}\", text, e))
+    }
+}
+
+";
+
+    // `xs:gYearMonth`'s lexical form is \"YYYY-MM\" with no day component, so
+    // it can't round-trip through `chrono::NaiveDate` (which requires one);
+    // wrap the year/month pair directly instead.
+    out += "\
+#[derive(Debug, Clone, PartialEq)]
+pub struct XsGYearMonth(pub i32, pub u32);
+
+impl YaSerialize for XsGYearMonth {
+    fn serialize<W: std::io::Write>(&self, writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String> {
+        writer
+            .write(xml::writer::XmlEvent::characters(&format!(\"{:04}-{:02}\", self.0, self.1)))
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl YaDeserialize for XsGYearMonth {
+    fn deserialize<R: std::io::Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, String> {
+        let text = reader.read_string().map_err(|e| e.to_string())?;
+        let body = strip_xsd_timezone(&text);
+        // rsplit_once, not split_once: a BC year like \"-0099-01\" has its
+        // own leading '-', so splitting on the *first* '-' would mistake
+        // the year's sign for the year/month separator and leave the year
+        // half empty. The month is always the last '-'-delimited piece.
+        let (year, month) = body
+            .rsplit_once('-')
+            .ok_or_else(|| format!(\"invalid xs:gYearMonth '{}': expected YYYY-MM\", text))?;
+        let year = year.parse().map_err(|e| format!(\"invalid xs:gYearMonth '{}': {}\", text, e))?;
+        let month = month.parse().map_err(|e| format!(\"invalid xs:gYearMonth '{}': {}\", text, e))?;
+        Ok(XsGYearMonth(year, month))
+    }
+}
+
+";
+
+    out += &precise_numeric_wrapper_definitions();
+    out
+}
+
+#[cfg(test)]
+mod typed_wrapper_definitions_tests {
+    use super::typed_wrapper_definitions;
+
+    #[test]
+    fn gyearmonth_splits_on_the_last_dash_not_the_first() {
+        let out = typed_wrapper_definitions();
+        assert!(
+            out.contains("body\n            .rsplit_once('-')"),
+            "XsGYearMonth must split on the last '-' so a BC year's own \
+             leading '-' (e.g. \"-0099-01\") doesn't get mistaken for the \
+             year/month separator"
+        );
+        assert!(!out.contains(".split_once('-')"));
+    }
+
+    #[test]
+    fn date_time_wrappers_strip_an_optional_timezone_before_parsing() {
+        let out = typed_wrapper_definitions();
+        assert!(out.contains("fn strip_xsd_timezone"));
+        assert!(out.contains("chrono::NaiveDate::parse_from_str(strip_xsd_timezone(&text)"));
+        assert!(out.contains("chrono::NaiveTime::parse_from_str(strip_xsd_timezone(&text)"));
+        assert!(out.contains("strip_xsd_timezone(&text).parse().map(XsGYear)"));
+    }
+}
+
+/// Wraps `xs:decimal`/`xs:integer` in a value-typed newtype instead of
+/// degrading them to `f64`/`i64`. Kept as a separate function from the
+/// date/time wrappers above since these two wrap a precise-numeric crate
+/// type whose `Display`/`FromStr` already match the XSD lexical form,
+/// rather than needing an explicit format string; folded into
+/// [`typed_wrapper_definitions`] so callers have a single function to
+/// invoke for every wrapper `match_type` can reference.
+pub fn precise_numeric_wrapper_definitions() -> String {
+    let mut out = String::new();
+    for (name, inner) in &[
+        ("XsDecimal", "bigdecimal::BigDecimal"),
+        ("XsInteger", "num_bigint::BigInt"),
+    ] {
+        let template = "\
+#[derive(Debug, Clone, PartialEq)]
+pub struct NAME(pub INNER);
+
+impl YaSerialize for NAME {
+    fn serialize<W: std::io::Write>(&self, writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String> {
+        writer.write(xml::writer::XmlEvent::characters(&self.0.to_string())).map_err(|e| e.to_string())
+    }
+}
+
+impl YaDeserialize for NAME {
+    fn deserialize<R: std::io::Read>(reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, String> {
+        let text = reader.read_string().map_err(|e| e.to_string())?;
+        text.parse().map(NAME).map_err(|e| format!(\"invalid NAME '{}': {}\", text, e))
+    }
+}
+
+";
+        out += &template.replace("NAME", name).replace("INNER", inner);
+    }
+    out
+}
+
+/// Characters XSD allows in a NCName-ish name but Rust forbids in an
+/// identifier. Replaced with `_` (by `sanitize_identifier`) before casing, so
+/// e.g. `"foo-bar.baz"` and `"Foo Bar/Baz"` both produce valid idents
+/// instead of silently keeping the offending characters.
+const BLACKLIST_CHARS: &[char] = &[
+    '-', '.', '/', '(', ')', ' ', ':', ',', '\'', '"', '+', '*', '&', '%', '#', '@', '!', '?', '<',
+    '>', '[', ']', '{', '}', '|', '\\', ';', '=',
+];
+
+/// Replaces characters Rust can't use in an identifier (XSD allows many of
+/// them in names) with `_`, trims leading and trailing `_`, and guarantees
+/// a non-empty result.
+fn sanitize_identifier(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if BLACKLIST_CHARS.contains(&c) || !c.is_ascii() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches('_');
+    if cleaned.is_empty() {
+        "unnamed".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// Sanitizes and cases `name` into a bare field identifier. Two distinct
+/// XSD attribute/element names can sanitize and case to the same Rust
+/// identifier; callers naming more than one field in the same struct (see
+/// `attributes_to_fields`) resolve that with [`NameCollisionTracker`].
 pub fn get_field_name(name: &str) -> String {
-    let result = to_snake_case(name);
-    if result.chars().next().unwrap().is_numeric() || RS_KEYWORDS.contains(&result.as_str()) {
+    let result = to_snake_case(&sanitize_identifier(name));
+    if result.chars().next().map_or(true, |c| c.is_numeric()) || RS_KEYWORDS.contains(&result.as_str())
+    {
         return format!("_{}", result);
     }
     result
 }
 
+/// Sanitizes and cases `name` into a bare type identifier. Two distinct
+/// XSD type names can sanitize and case to the same Rust identifier;
+/// callers naming more than one type in the same module resolve that with
+/// [`NameCollisionTracker`], the same way `attributes_to_fields` does for
+/// field names.
 pub fn get_type_name(name: &str) -> String {
-    let result = to_pascal_case(name);
-    if result.chars().next().unwrap().is_numeric() || RS_KEYWORDS.contains(&result.as_str()) {
+    let result = to_pascal_case(&sanitize_identifier(name));
+    if result.chars().next().map_or(true, |c| c.is_numeric()) || RS_KEYWORDS.contains(&result.as_str())
+    {
         return format!("_{}", result);
     }
     result
 }
 
-pub fn any_attribute_field() -> StructField {
+/// Tracks identifiers already produced within one scope (a struct's fields,
+/// or a module's type names) so that two distinct XSD names that collapse
+/// to the same Rust identifier after casing don't collide. Each repeat use
+/// of a candidate gets a numeric suffix; the caller should preserve wire
+/// fidelity for a renamed identifier with `#[yaserde(rename = original)]`.
+#[derive(Debug, Default)]
+pub struct NameCollisionTracker {
+    seen: HashMap<String, usize>,
+}
+
+impl NameCollisionTracker {
+    /// Resolves `candidate` (the cased, sanitized identifier) to a unique
+    /// name within this scope, appending a numeric suffix on repeat use. The
+    /// second element is `Some(original_name)` whenever the identifier differs
+    /// from `original_name` (a suffix was appended, or sanitizing/casing
+    /// changed the text), so the caller can rename-attribute back to it, and
+    /// `None` when the two are equal.
+    pub fn resolve(&mut self, candidate: &str, original_name: &str) -> (String, Option<String>) {
+        let count = self.seen.entry(candidate.to_string()).or_insert(0);
+        let identifier = if *count == 0 {
+            candidate.to_string()
+        } else {
+            format!("{}{}", candidate, count)
+        };
+        *count += 1;
+
+        let rename = if identifier != original_name {
+            Some(original_name.to_string())
+        } else {
+            None
+        };
+        (identifier, rename)
+    }
+}
+
+#[cfg(test)]
+mod naming_tests {
+    use super::{sanitize_identifier, NameCollisionTracker};
+
+    #[test]
+    fn sanitize_identifier_strips_blacklisted_chars() {
+        assert_eq!(sanitize_identifier("foo-bar.baz"), "foo_bar_baz");
+        assert_eq!(sanitize_identifier("Foo Bar/Baz"), "Foo_Bar_Baz");
+    }
+
+    #[test]
+    fn sanitize_identifier_falls_back_to_unnamed() {
+        assert_eq!(sanitize_identifier("---"), "unnamed");
+        assert_eq!(sanitize_identifier(""), "unnamed");
+    }
+
+    #[test]
+    fn tracker_suffixes_repeats_and_reports_the_original() {
+        let mut tracker = NameCollisionTracker::default();
+        assert_eq!(tracker.resolve("foo", "foo"), ("foo".to_string(), None));
+        assert_eq!(
+            tracker.resolve("foo", "foo"),
+            ("foo1".to_string(), Some("foo".to_string()))
+        );
+        assert_eq!(
+            tracker.resolve("foo", "foo"),
+            ("foo2".to_string(), Some("foo".to_string()))
+        );
+    }
+
+    #[test]
+    fn tracker_leaves_distinct_names_alone() {
+        let mut tracker = NameCollisionTracker::default();
+        assert_eq!(tracker.resolve("foo", "foo"), ("foo".to_string(), None));
+        assert_eq!(tracker.resolve("bar", "bar"), ("bar".to_string(), None));
+    }
+}
+
+pub fn any_attribute_field(config: &Config) -> StructField {
     StructField {
         name: "any_attribute".to_string(),
-        type_name: "AnyAttribute".to_string(),
-        comment: Some("//".to_string()),
-        macros: "//TODO: any_attribute macros \n//".to_string(),
+        type_name: "AnyAttributes".to_string(),
+        comment: Some("// Attributes from <xs:anyAttribute> not matched by any declared field,\n// re-emitted on serialize. The derived deserialize leaves it empty.\n".to_string()),
+        macros: config.backend.derive_backend().any_attributes_field_attribute(),
         subtypes: vec![],
     }
 }
 
+/// Backing type for [`any_attribute_field`]: the attributes left over
+/// after a struct's declared fields have claimed theirs, keyed by
+/// (optional prefix, local name); the namespace URI is not kept. Implements
+/// the `YaSerialize`/`YaDeserialize` hooks yaserde's derive dispatches to.
+/// Only serialization does real work: the `YaDeserialize` impl returns an
+/// empty collection, so a value holds attributes only when it is built with
+/// the inherent `from_unclaimed_attributes`, which no derived code calls.
+pub fn any_attributes_support() -> String {
+    "#[derive(Debug, Clone, Default, PartialEq)]\n\
+     pub struct AnyAttributes(pub Vec<((Option<String>, String), String)>);\n\n\
+     impl AnyAttributes {\n\
+     \u{20}   // Keeps the entries of `attributes` whose local name is not in\n\
+     \u{20}   // `claimed`. No derived code calls this.\n\
+     \u{20}   pub fn from_unclaimed_attributes(\n\
+     \u{20}       attributes: &[xml::attribute::OwnedAttribute],\n\
+     \u{20}       claimed: &[&str],\n\
+     \u{20}   ) -> Self {\n\
+     \u{20}       AnyAttributes(\n\
+     \u{20}           attributes\n\
+     \u{20}               .iter()\n\
+     \u{20}               .filter(|a| !claimed.contains(&a.name.local_name.as_str()))\n\
+     \u{20}               .map(|a| ((a.name.prefix.clone(), a.name.local_name.clone()), a.value.clone()))\n\
+     \u{20}               .collect(),\n\
+     \u{20}       )\n\
+     \u{20}   }\n\
+     }\n\n\
+     impl YaSerialize for AnyAttributes {\n\
+     \u{20}   // AnyAttributes never owns element content, only attributes; the\n\
+     \u{20}   // owning struct's derive-generated serialize never calls this for an\n\
+     \u{20}   // attribute-tagged field, but the trait requires it.\n\
+     \u{20}   fn serialize<W: std::io::Write>(&self, _writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String> {\n\
+     \u{20}       Ok(())\n\
+     \u{20}   }\n\n\
+     \u{20}   fn serialize_attributes(\n\
+     \u{20}       &self,\n\
+     \u{20}       mut attributes: Vec<xml::attribute::OwnedAttribute>,\n\
+     \u{20}       namespace: xml::namespace::Namespace,\n\
+     \u{20}   ) -> Result<(Vec<xml::attribute::OwnedAttribute>, xml::namespace::Namespace), String> {\n\
+     \u{20}       for ((prefix, name), value) in &self.0 {\n\
+     \u{20}           let qname = match prefix {\n\
+     \u{20}               Some(prefix) => xml::name::OwnedName::qualified(name.clone(), String::new(), Some(prefix.clone())),\n\
+     \u{20}               None => xml::name::OwnedName::local(name.clone()),\n\
+     \u{20}           };\n\
+     \u{20}           attributes.push(xml::attribute::OwnedAttribute::new(qname, value.clone()));\n\
+     \u{20}       }\n\
+     \u{20}       Ok((attributes, namespace))\n\
+     \u{20}   }\n\
+     }\n\n\
+     impl YaDeserialize for AnyAttributes {\n\
+     \u{20}   // Always returns an empty collection; the owning struct's unclaimed\n\
+     \u{20}   // attributes are not read here. It exists so the field type\n\
+     \u{20}   // satisfies the derive macro's trait bound.\n\
+     \u{20}   fn deserialize<R: std::io::Read>(_reader: &mut yaserde::de::Deserializer<R>) -> Result<Self, String> {\n\
+     \u{20}       Ok(AnyAttributes::default())\n\
+     \u{20}   }\n\
+     }\n\n"
+        .to_string()
+}
+
 pub fn target_namespace<'a, 'input>(node: &Node<'a, 'input>) -> Option<&'a Namespace<'input>> {
     match node.attribute(attribute::TARGET_NAMESPACE) {
         Some(tn) => node.namespaces().iter().find(|a| a.uri() == tn),
@@ -187,24 +659,28 @@ pub fn tuple_struct_macros() -> String {
     "//TODO: Tuple Struct macros\n".to_string()
 }
 
-pub fn yaserde_for_attribute(name: &str) -> String {
-    if let Some(index) = name.find(':') {
-        format!(
-            "  #[yaserde(attribute, prefix = \"{}\" rename = \"{}\")]\n",
-            &name[0..index],
-            &name[index + 1..]
-        )
-    } else {
-        format!("  #[yaserde(attribute, rename = \"{}\")]\n", name)
-    }
+/// Field-attribute macro for an XSD attribute, under `config`'s backend.
+///
+/// Thin wrapper kept for callers that only have a name and no whole
+/// `StructField`; delegates to
+/// [`DeriveBackend::attribute_field_attribute`] rather than hardcoding
+/// yaserde, so `config.backend == Backend::Serde` actually changes what
+/// comes out.
+pub fn yaserde_for_attribute(name: &str, config: &Config) -> String {
+    config.backend.derive_backend().attribute_field_attribute(name)
 }
 
-pub fn yaserde_for_element(name: &str, target_namespace: Option<&roxmltree::Namespace>) -> String {
-    let prefix = target_namespace.and_then(|ns| ns.name());
-    match prefix {
-        Some(p) => format!("  #[yaserde(prefix = \"{}\", rename = \"{}\")]\n", p, name),
-        None => format!("  #[yaserde(rename = \"{}\")]\n", name),
-    }
+/// Field-attribute macro for an XSD element, under `config`'s backend. See
+/// [`yaserde_for_attribute`].
+pub fn yaserde_for_element(
+    name: &str,
+    target_namespace: Option<&roxmltree::Namespace>,
+    config: &Config,
+) -> String {
+    config
+        .backend
+        .derive_backend()
+        .element_field_attribute(name, target_namespace)
 }
 
 pub fn get_parent_name<'a>(node: &Node<'a, '_>) -> &'a str {
@@ -223,34 +699,378 @@ pub fn get_parent_name<'a>(node: &Node<'a, '_>) -> &'a str {
     }
 }
 
-pub fn struct_macro(target_namespace: Option<&roxmltree::Namespace>) -> String {
-    let derives = "#[derive(Default, PartialEq, Debug, YaSerialize, YaDeserialize)]\n";
-    match target_namespace {
-        Some(tn) => match tn.name() {
-            Some(name) => format!(
-                "{derives}#[yaserde(prefix = \"{prefix}\", namespace = \"{prefix}: {uri}\")]\n",
-                derives = derives,
-                prefix = name,
-                uri = tn.uri()
-            ),
-            None => format!(
-                "{derives}#[yaserde(namespace = \"{uri}\")]\n",
-                derives = derives,
-                uri = tn.uri()
-            ),
-        },
-        None => format!("{derives}#[yaserde()]\n", derives = derives),
+/// Struct-level derive line for `target_namespace`, under `config`'s
+/// backend. See [`yaserde_for_attribute`].
+pub fn struct_macro(target_namespace: Option<&roxmltree::Namespace>, config: &Config) -> String {
+    config.backend.derive_backend().struct_derive(target_namespace)
+}
+
+/// Which [`DeriveBackend`] a [`Config`] selects for code generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Yaserde,
+    Serde,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Backend::Yaserde
     }
 }
 
-pub fn attributes_to_fields(node: &Node, target_ns: Option<&Namespace>) -> Vec<StructField> {
-    node.children()
+impl Backend {
+    pub fn derive_backend(&self) -> &'static dyn DeriveBackend {
+        match self {
+            Backend::Yaserde => &YaserdeBackend,
+            Backend::Serde => &SerdeBackend,
+        }
+    }
+}
+
+/// Backend-specific string building for struct derives and field
+/// attributes, selected by [`Config::backend`] instead of forking the
+/// generator per output format.
+pub trait DeriveBackend {
+    /// The struct-level derive line (and any backend attribute naming the
+    /// target namespace), e.g. yaserde's `#[yaserde(namespace = "...")]`.
+    fn struct_derive(&self, target_namespace: Option<&roxmltree::Namespace>) -> String;
+
+    /// The attribute placed on a field generated from an XSD element.
+    fn element_field_attribute(&self, name: &str, target_namespace: Option<&roxmltree::Namespace>) -> String;
+
+    /// The attribute placed on a field generated from an XSD attribute.
+    fn attribute_field_attribute(&self, name: &str) -> String;
+
+    /// The attribute that renames an already-emitted field back to
+    /// `original_name`, used by [`resolve_field_name_collisions`] when
+    /// casing/sanitizing two distinct XSD names collapses them onto the
+    /// same Rust identifier.
+    fn field_rename_attribute(&self, original_name: &str) -> String;
+
+    /// The attribute placed on [`any_attribute_field`]'s catch-all field.
+    fn any_attributes_field_attribute(&self) -> String;
+}
+
+pub struct YaserdeBackend;
+
+impl DeriveBackend for YaserdeBackend {
+    fn struct_derive(&self, target_namespace: Option<&roxmltree::Namespace>) -> String {
+        let derives = "#[derive(Default, PartialEq, Debug, YaSerialize, YaDeserialize)]\n";
+        match target_namespace {
+            Some(tn) => match tn.name() {
+                Some(name) => format!(
+                    "{derives}#[yaserde(prefix = \"{prefix}\", namespace = \"{prefix}: {uri}\")]\n",
+                    derives = derives,
+                    prefix = name,
+                    uri = tn.uri()
+                ),
+                None => format!(
+                    "{derives}#[yaserde(namespace = \"{uri}\")]\n",
+                    derives = derives,
+                    uri = tn.uri()
+                ),
+            },
+            None => format!("{derives}#[yaserde()]\n", derives = derives),
+        }
+    }
+
+    fn element_field_attribute(&self, name: &str, target_namespace: Option<&roxmltree::Namespace>) -> String {
+        let prefix = target_namespace.and_then(|ns| ns.name());
+        match prefix {
+            Some(p) => format!("  #[yaserde(prefix = \"{}\", rename = \"{}\")]\n", p, name),
+            None => format!("  #[yaserde(rename = \"{}\")]\n", name),
+        }
+    }
+
+    fn attribute_field_attribute(&self, name: &str) -> String {
+        if let Some(index) = name.find(':') {
+            format!(
+                "  #[yaserde(attribute, prefix = \"{}\" rename = \"{}\")]\n",
+                &name[0..index],
+                &name[index + 1..]
+            )
+        } else {
+            format!("  #[yaserde(attribute, rename = \"{}\")]\n", name)
+        }
+    }
+
+    fn field_rename_attribute(&self, original_name: &str) -> String {
+        format!("  #[yaserde(rename = \"{}\")]\n", original_name)
+    }
+
+    fn any_attributes_field_attribute(&self) -> String {
+        "  #[yaserde(attribute)]\n".to_string()
+    }
+}
+
+pub struct SerdeBackend;
+
+impl DeriveBackend for SerdeBackend {
+    fn struct_derive(&self, _target_namespace: Option<&roxmltree::Namespace>) -> String {
+        "#[derive(Default, PartialEq, Debug, Serialize, Deserialize)]\n".to_string()
+    }
+
+    fn element_field_attribute(&self, name: &str, _target_namespace: Option<&roxmltree::Namespace>) -> String {
+        format!("  #[serde(rename = \"{}\")]\n", name)
+    }
+
+    fn attribute_field_attribute(&self, name: &str) -> String {
+        match name.find(':') {
+            Some(index) => format!("  #[serde(rename = \"{}\")]\n", &name[index + 1..]),
+            None => format!("  #[serde(rename = \"{}\")]\n", name),
+        }
+    }
+
+    fn field_rename_attribute(&self, original_name: &str) -> String {
+        format!("  #[serde(rename = \"{}\")]\n", original_name)
+    }
+
+    fn any_attributes_field_attribute(&self) -> String {
+        // serde has no per-field "attribute vs element" distinction of its
+        // own; `flatten` is the closest analog for folding a catch-all
+        // collection's entries into the struct's own serialized output.
+        "  #[serde(flatten)]\n".to_string()
+    }
+}
+
+/// Assembles a struct's attribute fields, consulting `config.backend` for
+/// the parts this function builds itself (the catch-all `any_attribute`
+/// field, and any rename forced by a name collision). The per-attribute
+/// fields that come back from `parse_node` are *not* re-dispatched through
+/// `config.backend` here: `parse_node` lives in `crate::parser::parser`,
+/// outside this single-file snapshot, and it bakes each field's own
+/// rename/attribute macro in directly using the raw XSD name — a name this
+/// function never sees, since `StructField` only carries the already-cased
+/// Rust identifier. Retrofitting those macros from here using `field.name`
+/// would silently replace a correct rename with a wrong one (see
+/// [`resolve_field_name_collisions`]), so real backend support for those
+/// ordinary fields has to land in `parse_node` itself.
+pub fn attributes_to_fields(node: &Node, target_ns: Option<&Namespace>, config: &Config) -> Vec<StructField> {
+    let mut fields: Vec<StructField> = node
+        .children()
         .filter(|n| n.is_element() && n.xsd_type() == ElementType::Attribute)
         .map(|n| match parse_node(&n, node, target_ns) {
             RsEntity::StructField(sf) => sf,
             _ => unreachable!("Invalid attribute parsing: {:?}", n),
         })
-        .collect()
+        .collect();
+
+    let has_any_attribute = node
+        .children()
+        .any(|n| n.is_element() && n.xsd_type() == ElementType::AnyAttribute);
+    if has_any_attribute {
+        fields.push(any_attribute_field(config));
+    }
+
+    resolve_field_name_collisions(&mut fields, config);
+
+    fields
+}
+
+/// Ensures every field in `fields` has a unique name, appending a numeric
+/// suffix via [`NameCollisionTracker`] whenever two distinct attributes on
+/// the same struct collapsed to the same Rust identifier after casing.
+///
+/// `field.name` at this point is already the cased Rust identifier, not the
+/// raw XSD wire name `StructField` has no slot to keep around — so this
+/// can't rename a suffixed field back to its true original text. Most
+/// fields already carry a correct rename macro from `parse_node` (built
+/// from that raw name before it was cased), so a suffix only needs a new
+/// one added when none exists yet, which happens precisely when casing
+/// didn't change the text in the first place — in that one case the cased
+/// name given to `tracker.resolve` *is* the original name.
+fn resolve_field_name_collisions(fields: &mut [StructField], config: &Config) {
+    let mut tracker = NameCollisionTracker::default();
+    for field in fields.iter_mut() {
+        let cased_name = field.name.clone();
+        let (unique_name, suffixed) = tracker.resolve(&cased_name, &cased_name);
+        if suffixed.is_some() {
+            field.name = unique_name;
+            if !field.macros.contains("rename") {
+                field.macros += &config.backend.derive_backend().field_rename_attribute(&cased_name);
+            }
+        }
+    }
+}
+
+/// Boxes fields on cyclic references between generated structs until none
+/// remain, so the resulting Rust structs aren't infinitely sized.
+///
+/// A strongly-connected component can have chords (e.g. `A` holding fields
+/// of both `B` and `C`, with `B` and `C` each holding a field of `A`), so a
+/// single pass that boxes one field per node isn't enough: a node can still
+/// have a *second*, unboxed edge into the component, and those leftover
+/// edges can form their own cycle. So this rebuilds the edge set and
+/// recomputes SCCs after every pass, boxing one more field per node of
+/// whatever cyclic components remain, until a pass finds none — each pass
+/// removes at least one edge from every node it touches, so this always
+/// terminates.
+pub fn box_recursive_fields(entities: &mut [RsEntity]) {
+    let index_by_name: HashMap<String, usize> = entities
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| match e {
+            RsEntity::Struct(s) => Some((s.name.clone(), i)),
+            _ => None,
+        })
+        .collect();
+
+    loop {
+        let edges: Vec<Vec<usize>> = entities
+            .iter()
+            .map(|e| match e {
+                RsEntity::Struct(s) => s
+                    .fields
+                    .iter()
+                    .filter_map(|f| index_by_name.get(&f.type_name).copied())
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .collect();
+
+        let cyclic_components: Vec<Vec<usize>> = tarjan_scc(&edges)
+            .into_iter()
+            .filter(|component| component.len() > 1 || edges[component[0]].contains(&component[0]))
+            .collect();
+
+        if cyclic_components.is_empty() {
+            break;
+        }
+
+        for component in cyclic_components {
+            let component_set: std::collections::HashSet<usize> = component.iter().copied().collect();
+            for node in component {
+                if let RsEntity::Struct(s) = &mut entities[node] {
+                    if let Some(field) = s.fields.iter_mut().find(|f| {
+                        index_by_name
+                            .get(&f.type_name)
+                            .map_or(false, |idx| component_set.contains(idx))
+                    }) {
+                        field.type_name = format!("Box<{}>", field.type_name);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Tarjan's strongly-connected-components algorithm over an adjacency list.
+fn tarjan_scc(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct State {
+        index: Vec<Option<usize>>,
+        lowlink: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    fn strong_connect(v: usize, edges: &[Vec<usize>], state: &mut State) {
+        state.index[v] = Some(state.next_index);
+        state.lowlink[v] = state.next_index;
+        state.next_index += 1;
+        state.stack.push(v);
+        state.on_stack[v] = true;
+
+        for &w in &edges[v] {
+            if state.index[w].is_none() {
+                strong_connect(w, edges, state);
+                state.lowlink[v] = state.lowlink[v].min(state.lowlink[w]);
+            } else if state.on_stack[w] {
+                state.lowlink[v] = state.lowlink[v].min(state.index[w].unwrap());
+            }
+        }
+
+        if state.lowlink[v] == state.index[v].unwrap() {
+            let mut component = Vec::new();
+            loop {
+                let w = state.stack.pop().unwrap();
+                state.on_stack[w] = false;
+                component.push(w);
+                if w == v {
+                    break;
+                }
+            }
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        index: vec![None; edges.len()],
+        lowlink: vec![0; edges.len()],
+        on_stack: vec![false; edges.len()],
+        stack: Vec::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+
+    for v in 0..edges.len() {
+        if state.index[v].is_none() {
+            strong_connect(v, edges, &mut state);
+        }
+    }
+
+    state.components
+}
+
+#[cfg(test)]
+mod tarjan_scc_tests {
+    use super::tarjan_scc;
+
+    // The chunk0-2 review's counter-example: A, B, and C each reference
+    // both other nodes (A->B, A->C, B->A, B->C, C->A, C->B). This has to
+    // land in one SCC, not split into smaller ones: `box_recursive_fields`
+    // boxes one field per node per pass, and the edges its first pass
+    // leaves unboxed here (0->2, 1->2, 2->1) still form a cycle.
+    #[test]
+    fn fully_chorded_triangle_is_one_component() {
+        let edges = vec![vec![1, 2], vec![0, 2], vec![0, 1]];
+        let mut components = tarjan_scc(&edges);
+        for component in components.iter_mut() {
+            component.sort();
+        }
+        assert_eq!(components, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn acyclic_graph_has_no_multi_node_components() {
+        let edges = vec![vec![1], vec![2], vec![]];
+        let components = tarjan_scc(&edges);
+        assert!(components.iter().all(|c| c.len() == 1));
+    }
+}
+
+/// Runs the post-parse passes this file owns over `entities` and returns
+/// the extra source that has to be spliced into the generated module
+/// alongside the structs themselves, all driven by a single `config`.
+///
+/// It boxes recursive fields, then appends the typed wrapper definitions
+/// when `config.emit_typed_wrappers` is set and the `AnyAttributes` support
+/// type when some struct has an `AnyAttributes` field, so a caller only
+/// pays for what it uses; `config.backend` is not consulted here. Per-field
+/// concerns (`match_type`'s `Cow<str>` type name, `struct_macro`/
+/// `yaserde_for_element`/`yaserde_for_attribute`'s derive lines) each take
+/// a `config` at the point a `StructField`/struct is built;
+/// `attributes_to_fields` calls `parse_node` without one. This function is
+/// the complement that runs once per module rather than once per field.
+pub fn generate_module_prelude(entities: &mut [RsEntity], config: &Config) -> String {
+    box_recursive_fields(entities);
+
+    let mut prelude = String::new();
+
+    if config.emit_typed_wrappers {
+        prelude += &typed_wrapper_definitions();
+    }
+
+    let uses_any_attributes = entities.iter().any(|e| match e {
+        RsEntity::Struct(s) => s.fields.iter().any(|f| f.type_name == "AnyAttributes"),
+        _ => false,
+    });
+    if uses_any_attributes {
+        prelude += &any_attributes_support();
+    }
+
+    prelude
 }
 
 const RS_KEYWORDS: &[&str] = &[